@@ -0,0 +1,280 @@
+//! Derive macro companion to `errors_convention`.
+//!
+//! Annotate a domain error enum's variants with `#[api_error(code = "...", message = "...")]`
+//! (and an optional `reason = "..."`) to generate `impl From<YourError> for ApiError`. The
+//! message template may reference the variant's fields, e.g. `"user {id} not found"`; those
+//! fields are interpolated into `ApiError.message` and also recorded as `ErrorInfo.metadata`,
+//! so the dynamic parts of the message live in `details` per the convention documented on
+//! `ApiError::message`.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+#[proc_macro_derive(IntoApiError, attributes(api_error))]
+pub fn derive_into_api_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct VariantSpec {
+    code: syn::Ident,
+    message: LitStr,
+    reason: Option<LitStr>,
+}
+
+fn parse_variant_attr(attrs: &[syn::Attribute]) -> syn::Result<VariantSpec> {
+    let attr = attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("api_error"))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(attrs.first(), "variant is missing #[api_error(code = \"...\", message = \"...\")]")
+        })?;
+
+    let mut code = None;
+    let mut message = None;
+    let mut reason = None;
+
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("code") {
+            let value: LitStr = meta.value()?.parse()?;
+            code = Some(format_ident!("{}", value.value(), span = value.span()));
+        } else if meta.path.is_ident("message") {
+            message = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("reason") {
+            reason = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("unsupported `api_error` key, expected `code`, `message` or `reason`"));
+        }
+        Ok(())
+    })?;
+
+    Ok(VariantSpec {
+        code: code.ok_or_else(|| syn::Error::new_spanned(attr, "`api_error` is missing `code = \"...\"`"))?,
+        message: message.ok_or_else(|| syn::Error::new_spanned(attr, "`api_error` is missing `message = \"...\"`"))?,
+        reason,
+    })
+}
+
+/// A `{name}` or `{name:spec}` placeholder parsed out of a message template.
+struct TemplateField {
+    /// The full placeholder body between `{` and `}`, e.g. `"amount:.2"`.
+    raw: String,
+    /// Just the field/index portion, with any `:spec` stripped, e.g. `"amount"`.
+    name: String,
+}
+
+/// Splits a `"price is {amount:.2}"` template into the placeholders referenced inside its
+/// `{}` braces, in order of appearance. A placeholder's format spec (the part from the first
+/// `:` onward, same as `format!`'s own syntax) is kept on [`TemplateField::raw`] but stripped
+/// from [`TemplateField::name`], since the name is what has to become a Rust identifier.
+fn template_fields(template: &str) -> Vec<TemplateField> {
+    let mut fields = Vec::new();
+    let mut chars = template.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        if ch != '{' {
+            continue;
+        }
+        let mut raw = String::new();
+        for (_, next) in chars.by_ref() {
+            if next == '}' {
+                break;
+            }
+            raw.push(next);
+        }
+        if raw.is_empty() {
+            continue;
+        }
+        let name = raw.split(':').next().unwrap_or_default().to_string();
+        fields.push(TemplateField { raw, name });
+    }
+    fields
+}
+
+/// Removes placeholders whose name repeats an earlier one (e.g. `"{id} ... {id}"`), keeping
+/// first-seen order. `format!` rejects a named/positional argument supplied more than once in
+/// the macro call, even though the template itself may reference a name any number of times.
+fn dedup_fields(fields: Vec<TemplateField>) -> Vec<TemplateField> {
+    let mut seen = std::collections::HashSet::new();
+    fields.into_iter().filter(|field| seen.insert(field.name.clone())).collect()
+}
+
+/// Parses a placeholder's (spec-stripped) name as a Rust identifier, returning a clean
+/// `syn::Error` instead of letting `format_ident!` panic on a name that isn't one (e.g.
+/// `{user id}`).
+fn field_ident(field: &TemplateField, message: &LitStr) -> syn::Result<syn::Ident> {
+    syn::parse_str(&field.name).map_err(|_| {
+        syn::Error::new_spanned(message, format!("`{{{}}}` in the message template is not a valid field name", field.raw))
+    })
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let enum_name = &input.ident;
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input, "IntoApiError can only be derived for enums"));
+    };
+
+    let mut arms = Vec::with_capacity(data.variants.len());
+    #[allow(unused_mut)]
+    let mut registrations: Vec<proc_macro2::TokenStream> = Vec::new();
+
+    for variant in &data.variants {
+        let spec = parse_variant_attr(&variant.attrs)?;
+        let variant_name = &variant.ident;
+        let code = &spec.code;
+        let message_template = spec.message.value();
+        let fields = dedup_fields(template_fields(&message_template));
+
+        let (pattern, metadata_inserts, format_args, rewritten_template) = match &variant.fields {
+            Fields::Named(named) => {
+                let idents: Vec<_> = named.named.iter().map(|f| f.ident.clone().unwrap()).collect();
+                let pattern = quote! { #enum_name::#variant_name { #(#idents),* } };
+                let mut metadata_inserts = Vec::with_capacity(fields.len());
+                let mut format_args = Vec::with_capacity(fields.len());
+                for field in &fields {
+                    let ident = field_ident(field, &spec.message)?;
+                    let name = &field.name;
+                    metadata_inserts.push(quote! { metadata.insert(#name.to_string(), #ident.to_string()); });
+                    format_args.push(quote! { #ident = #ident });
+                }
+                (pattern, metadata_inserts, format_args, message_template.clone())
+            }
+            Fields::Unnamed(unnamed) => {
+                // Tuple variants are referenced positionally in the template (e.g. "{0}" or
+                // "{0:?}"), but `format!` requires named captures to line up with only the
+                // arguments actually used, so the template is rewritten to named placeholders
+                // (`{field_0}`, format spec preserved) bound to the matched fields.
+                let idents: Vec<_> = (0..unnamed.unnamed.len()).map(|i| format_ident!("field_{i}")).collect();
+                let pattern = quote! { #enum_name::#variant_name(#(#idents),*) };
+                let mut rewritten_template = message_template.clone();
+                let mut metadata_inserts = Vec::with_capacity(fields.len());
+                let mut format_args = Vec::with_capacity(fields.len());
+                for field in &fields {
+                    if field.name.parse::<usize>().is_err() {
+                        return Err(syn::Error::new_spanned(
+                            &spec.message,
+                            format!("`{{{}}}` is not a valid positional placeholder for a tuple variant; expected a field index like `{{0}}`", field.raw),
+                        ));
+                    }
+                    let ident = format_ident!("field_{}", field.name);
+                    let name = &field.name;
+                    metadata_inserts.push(quote! { metadata.insert(#name.to_string(), #ident.to_string()); });
+                    format_args.push(quote! { #ident = #ident });
+                }
+                // Rewritten from the undeduplicated placeholders, so a repeated index with a
+                // different spec (e.g. "{0} ({0:?})") is rewritten too.
+                for field in template_fields(&message_template) {
+                    let spec_suffix = &field.raw[field.name.len()..];
+                    rewritten_template = rewritten_template
+                        .replace(&format!("{{{}}}", field.raw), &format!("{{field_{}{spec_suffix}}}", field.name));
+                }
+                (pattern, metadata_inserts, format_args, rewritten_template)
+            }
+            Fields::Unit => (quote! { #enum_name::#variant_name }, Vec::new(), Vec::new(), message_template.clone()),
+        };
+
+        let reason_str = spec.reason.as_ref().map(LitStr::value).unwrap_or_else(|| variant_name.to_string());
+
+        arms.push(quote! {
+            #pattern => {
+                let message = format!(#rewritten_template, #(#format_args),*);
+                let mut metadata = ::std::collections::HashMap::new();
+                #(#metadata_inserts)*
+                errors_convention::ApiError::new(errors_convention::ErrorCode::#code, message.clone())
+                    .add_detail(errors_convention::ErrorDetails::ErrorInfo { reason: #reason_str.to_string(), metadata })
+            }
+        });
+
+        #[cfg(feature = "summary")]
+        registrations.push(quote! {
+            errors_convention::inventory::submit! {
+                errors_convention::ErrorDescriptor {
+                    http_status: errors_convention::ErrorCode::#code.get_http_code().as_u16(),
+                    grpc_code: errors_convention::ErrorCode::#code.grpc_code(),
+                    code: errors_convention::ErrorCode::#code,
+                    reason: #reason_str,
+                    raw_message: #message_template,
+                }
+            }
+        });
+    }
+
+    Ok(quote! {
+        impl ::std::convert::From<#enum_name> for errors_convention::ApiError {
+            fn from(error: #enum_name) -> Self {
+                match error {
+                    #(#arms)*
+                }
+            }
+        }
+
+        #(#registrations)*
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn field_names(template: &str) -> Vec<String> {
+        dedup_fields(template_fields(template)).into_iter().map(|field| field.name).collect()
+    }
+
+    #[test]
+    fn template_fields_strips_format_specs_from_the_name() {
+        assert_eq!(field_names("price is {amount:.2}"), vec!["amount"]);
+        assert_eq!(field_names("item {0:?} missing at index {1}"), vec!["0", "1"]);
+    }
+
+    #[test]
+    fn dedup_fields_keeps_first_seen_order() {
+        assert_eq!(field_names("request {id} failed for user {id}"), vec!["id"]);
+        assert_eq!(field_names("{b} then {a} then {b}"), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn field_ident_rejects_an_unstrippable_name_instead_of_panicking() {
+        // format_ident!("{}", "amount:.2") panics; field_ident must report it as a syn::Error.
+        let field = TemplateField { raw: "amount:.2".to_string(), name: "amount:.2".to_string() };
+        let message: LitStr = parse_quote!("unused");
+        assert!(field_ident(&field, &message).is_err());
+    }
+
+    #[test]
+    fn expand_dedupes_a_repeated_named_placeholder_in_the_format_call() {
+        let input: DeriveInput = parse_quote! {
+            enum MyError {
+                #[api_error(code = "NotFound", message = "request {id} failed for user {id}")]
+                NotFound { id: u64 },
+            }
+        };
+        let tokens = expand(input).unwrap().to_string();
+        assert_eq!(tokens.matches("id = id").count(), 1);
+    }
+
+    #[test]
+    fn expand_rewrites_tuple_placeholders_while_keeping_their_format_spec() {
+        let input: DeriveInput = parse_quote! {
+            enum MyError {
+                #[api_error(code = "NotFound", message = "item {0:?} missing")]
+                Missing(String),
+            }
+        };
+        let tokens = expand(input).unwrap().to_string();
+        assert!(tokens.contains("field_0:?"), "tokens did not contain rewritten spec'd placeholder: {tokens}");
+    }
+
+    #[test]
+    fn expand_rejects_a_non_numeric_tuple_placeholder() {
+        let input: DeriveInput = parse_quote! {
+            enum MyError {
+                #[api_error(code = "NotFound", message = "{name} missing")]
+                Missing(String),
+            }
+        };
+        assert!(expand(input).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//! Bridges `ApiError` onto a gRPC/HTTP2 boundary: the numeric `grpc-status`, the
+//! percent-encoded `grpc-message`, and a `grpc-status-details-bin` trailer carrying
+//! the `details` as an `Any`-wrapped `google.rpc.Status` protobuf.
+
+use crate::{ApiError, ErrorDetails};
+use axum::http;
+
+/// Minimal protobuf wire-format encoding, just enough to build a `google.rpc.Status`
+/// message without pulling in a full protobuf codegen dependency.
+mod proto {
+    pub fn varint(mut value: u64, buf: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                buf.push(byte);
+                break;
+            }
+            buf.push(byte | 0x80);
+        }
+    }
+
+    fn tag(field_number: u32, wire_type: u8, buf: &mut Vec<u8>) {
+        varint(((field_number as u64) << 3) | wire_type as u64, buf);
+    }
+
+    pub fn int32(field_number: u32, value: i32, buf: &mut Vec<u8>) {
+        if value == 0 {
+            return;
+        }
+        tag(field_number, 0, buf);
+        varint(value as u64, buf);
+    }
+
+    pub fn int64(field_number: u32, value: i64, buf: &mut Vec<u8>) {
+        if value == 0 {
+            return;
+        }
+        tag(field_number, 0, buf);
+        varint(value as u64, buf);
+    }
+
+    pub fn string(field_number: u32, value: &str, buf: &mut Vec<u8>) {
+        if value.is_empty() {
+            return;
+        }
+        tag(field_number, 2, buf);
+        varint(value.len() as u64, buf);
+        buf.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn bytes(field_number: u32, value: &[u8], buf: &mut Vec<u8>) {
+        tag(field_number, 2, buf);
+        varint(value.len() as u64, buf);
+        buf.extend_from_slice(value);
+    }
+
+    pub fn message(field_number: u32, value: &[u8], buf: &mut Vec<u8>) {
+        bytes(field_number, value, buf);
+    }
+
+    /// Encodes a `map<string, string>` entry as a length-delimited `{ key = 1, value = 2 }` message.
+    pub fn map_entry(field_number: u32, key: &str, value: &str, buf: &mut Vec<u8>) {
+        let mut entry = Vec::new();
+        string(1, key, &mut entry);
+        string(2, value, &mut entry);
+        message(field_number, &entry, buf);
+    }
+}
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/google.rpc.";
+
+/// Encodes a single `ErrorDetails` variant as its canonical `google.rpc.*` protobuf message,
+/// returning the message bytes together with the type name used to build its `Any.type_url`.
+fn encode_detail(detail: &ErrorDetails) -> (&'static str, Vec<u8>) {
+    let mut buf = Vec::new();
+    let type_name = match detail {
+        ErrorDetails::ErrorInfo { reason, metadata } => {
+            proto::string(1, reason, &mut buf);
+            for (key, value) in metadata {
+                proto::map_entry(3, key, value, &mut buf);
+            }
+            "ErrorInfo"
+        }
+        ErrorDetails::BadRequest { field_violations } => {
+            for violation in field_violations {
+                let mut entry = Vec::new();
+                proto::string(1, &violation.field, &mut entry);
+                proto::string(2, &violation.description, &mut entry);
+                proto::message(1, &entry, &mut buf);
+            }
+            "BadRequest"
+        }
+        ErrorDetails::LocalizedMessage { locale, message } => {
+            proto::string(1, locale, &mut buf);
+            proto::string(2, message, &mut buf);
+            "LocalizedMessage"
+        }
+        ErrorDetails::RetryInfo { retry_delay } => {
+            let mut duration = Vec::new();
+            proto::int64(1, retry_delay.seconds, &mut duration);
+            proto::int32(2, retry_delay.nanos, &mut duration);
+            proto::message(1, &duration, &mut buf);
+            "RetryInfo"
+        }
+        ErrorDetails::DebugInfo {
+            stack_entries,
+            detail,
+        } => {
+            for entry in stack_entries {
+                proto::string(1, entry, &mut buf);
+            }
+            proto::string(2, detail, &mut buf);
+            "DebugInfo"
+        }
+        ErrorDetails::QuotaFailure { violations } => {
+            for violation in violations {
+                let mut entry = Vec::new();
+                proto::string(1, &violation.subject, &mut entry);
+                proto::string(2, &violation.description, &mut entry);
+                proto::message(1, &entry, &mut buf);
+            }
+            "QuotaFailure"
+        }
+        ErrorDetails::PreconditionFailure { violations } => {
+            for violation in violations {
+                let mut entry = Vec::new();
+                proto::string(1, &violation.type_, &mut entry);
+                proto::string(2, &violation.subject, &mut entry);
+                proto::string(3, &violation.description, &mut entry);
+                proto::message(1, &entry, &mut buf);
+            }
+            "PreconditionFailure"
+        }
+        ErrorDetails::ResourceInfo {
+            resource_type,
+            resource_name,
+            owner,
+            description,
+        } => {
+            proto::string(1, resource_type, &mut buf);
+            proto::string(2, resource_name, &mut buf);
+            proto::string(3, owner, &mut buf);
+            proto::string(4, description, &mut buf);
+            "ResourceInfo"
+        }
+        ErrorDetails::RequestInfo {
+            request_id,
+            serving_data,
+        } => {
+            proto::string(1, request_id, &mut buf);
+            proto::string(2, serving_data, &mut buf);
+            "RequestInfo"
+        }
+        ErrorDetails::Help { links } => {
+            for link in links {
+                let mut entry = Vec::new();
+                proto::string(1, &link.description, &mut entry);
+                proto::string(2, &link.url, &mut entry);
+                proto::message(1, &entry, &mut buf);
+            }
+            "Help"
+        }
+    };
+
+    (type_name, buf)
+}
+
+/// Encodes a detail as a `google.protobuf.Any { type_url, value }` message.
+fn encode_any(detail: &ErrorDetails) -> Vec<u8> {
+    let (type_name, value) = encode_detail(detail);
+    let mut any = Vec::new();
+    proto::string(1, &format!("{TYPE_URL_PREFIX}{type_name}"), &mut any);
+    proto::bytes(2, &value, &mut any);
+    any
+}
+
+/// Encodes a `google.rpc.Status { code, message, details }` protobuf message.
+fn encode_status(grpc_code: i32, message: &str, details: &[&ErrorDetails]) -> Vec<u8> {
+    let mut status = Vec::new();
+    proto::int32(1, grpc_code, &mut status);
+    proto::string(2, message, &mut status);
+    for detail in details {
+        proto::message(3, &encode_any(detail), &mut status);
+    }
+    status
+}
+
+const BASE64_STD_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` using the standard (non-URL-safe), padded base64 alphabet.
+fn base64_std_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+
+        out.push(BASE64_STD_ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(BASE64_STD_ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_STD_ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_STD_ALPHABET[(b2 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+/// Percent-encodes `message` for the `grpc-message` trailer, using the CONTROLS set plus
+/// space, `"`, `#` and `%` itself (and any non-ASCII byte), matching the gRPC wire-format requirement
+/// that header values be visible ASCII.
+fn percent_encode_grpc_message(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    for byte in message.bytes() {
+        let needs_encoding = byte < 0x20
+            || byte == 0x7f
+            || matches!(byte, b' ' | b'"' | b'#' | b'%')
+            || byte >= 0x80;
+        if needs_encoding {
+            out.push_str(&format!("%{byte:02X}"));
+        } else {
+            out.push(byte as char);
+        }
+    }
+    out
+}
+
+impl ApiError {
+    /// Renders the error as the three gRPC trailer headers (`grpc-status`, `grpc-message`,
+    /// `grpc-status-details-bin`), so a single `ApiError` can drive both REST and gRPC transports.
+    /// `Internal`/`Unknown` errors are sanitized the same way as the axum JSON response, so the
+    /// gRPC path never leaks a developer-facing message or `DebugInfo`/`ErrorInfo` detail either.
+    pub fn grpc_trailers(&self) -> http::HeaderMap {
+        let mut trailers = http::HeaderMap::new();
+        let (message, details) = self.client_safe_message_and_details();
+        let grpc_code = self.code.grpc_code();
+
+        trailers.insert(
+            http::HeaderName::from_static("grpc-status"),
+            http::HeaderValue::from_str(&grpc_code.to_string()).expect("grpc status code is always a valid header value"),
+        );
+
+        if let Ok(value) = http::HeaderValue::from_str(&percent_encode_grpc_message(message)) {
+            trailers.insert(http::HeaderName::from_static("grpc-message"), value);
+        }
+
+        let details_bin = base64_std_encode(&encode_status(grpc_code, message, &details));
+        if let Ok(value) = http::HeaderValue::from_str(&details_bin) {
+            trailers.insert(
+                http::HeaderName::from_static("grpc-status-details-bin"),
+                value,
+            );
+        }
+
+        trailers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+
+    #[test]
+    fn grpc_trailers_sanitizes_internal_errors() {
+        let err = ApiError::from_source(
+            ErrorCode::Internal,
+            "insert into users failed",
+            std::io::Error::other("duplicate key value violates unique constraint"),
+        );
+
+        let trailers = err.grpc_trailers();
+
+        let message = trailers.get("grpc-message").unwrap().to_str().unwrap();
+        assert!(!message.contains("duplicate"), "leaked cause text in grpc-message: {message}");
+        assert!(!message.contains("insert"), "leaked developer message in grpc-message: {message}");
+
+        let details_bin = trailers.get("grpc-status-details-bin").unwrap().to_str().unwrap();
+        let expected = base64_std_encode(&encode_status(ErrorCode::Internal.grpc_code(), ApiError::SANITIZED_MESSAGE, &[]));
+        assert_eq!(details_bin, expected);
+    }
+
+    #[test]
+    fn grpc_trailers_pass_through_non_internal_errors() {
+        let err = ApiError::new(ErrorCode::NotFound, "user 1 not found");
+
+        let trailers = err.grpc_trailers();
+
+        let message = trailers.get("grpc-message").unwrap().to_str().unwrap();
+        assert_eq!(message, "user%201%20not%20found");
+    }
+}
@@ -1,6 +1,21 @@
 use axum::{http, Json};
 
-#[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+mod grpc;
+#[cfg(feature = "summary")]
+mod summary;
+
+/// Derives `impl From<YourError> for ApiError` from `#[api_error(code = "...", message = "...")]`
+/// annotations on each variant. See the `errors-convention-derive` crate docs for details.
+pub use errors_convention_derive::IntoApiError;
+
+#[cfg(feature = "summary")]
+pub use summary::{summary, ErrorDescriptor};
+/// Re-exported so the `IntoApiError` derive can register into the catalog without requiring
+/// callers to add `inventory` as a direct dependency.
+#[cfg(feature = "summary")]
+pub use inventory;
+
+#[derive(Debug, Clone, Copy, thiserror::Error, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ErrorCode {
     /// The client specified an invalid argument regardless of the state of the system.
@@ -37,7 +52,7 @@ pub enum ErrorCode {
 }
 
 impl ErrorCode {
-    pub fn get_http_code(&self) -> http::StatusCode {
+    pub const fn get_http_code(&self) -> http::StatusCode {
         match self {
             ErrorCode::InvalidArgument => http::StatusCode::BAD_REQUEST,
             ErrorCode::FailedPrecondition => http::StatusCode::BAD_REQUEST,
@@ -51,6 +66,33 @@ impl ErrorCode {
             ErrorCode::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
         }
     }
+
+    /// Maps the error code to its canonical `google.rpc.Code` integer, for use on a gRPC boundary.
+    pub const fn grpc_code(&self) -> i32 {
+        match self {
+            ErrorCode::Unknown => 2,
+            ErrorCode::InvalidArgument => 3,
+            ErrorCode::NotFound => 5,
+            ErrorCode::AlreadyExists => 6,
+            ErrorCode::PermissionDenied => 7,
+            ErrorCode::Unavailable => 14,
+            ErrorCode::FailedPrecondition => 9,
+            ErrorCode::TooManyRequests => 8, // ResourceExhausted
+            ErrorCode::Internal => 13,
+            ErrorCode::Unauthenticated => 16,
+        }
+    }
+
+    /// Default retry delay, in seconds, for a retryable code. Backs `ApiError::retry_after`
+    /// so `Unavailable`/`TooManyRequests` responses always carry retry guidance, even when the
+    /// caller hasn't explicitly attached one via `ApiError::with_retry_after`.
+    const fn default_retry_after_secs(&self) -> Option<i64> {
+        match self {
+            ErrorCode::Unavailable => Some(1),
+            ErrorCode::TooManyRequests => Some(30),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -59,12 +101,59 @@ pub struct FieldViolation {
     description: String,
 }
 
+/// A protobuf-style `google.protobuf.Duration`, used by [`ErrorDetails::RetryInfo`].
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Duration {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+impl Duration {
+    pub fn from_secs(seconds: i64) -> Self {
+        Self { seconds, nanos: 0 }
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct QuotaViolation {
+    pub subject: String,
+    pub description: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct PreconditionViolation {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub subject: String,
+    pub description: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct HelpLink {
+    pub description: String,
+    pub url: String,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "@type")]
 pub enum ErrorDetails {
     ErrorInfo { reason: String, metadata: std::collections::HashMap<String, String>},
     BadRequest { field_violations: Vec<FieldViolation> },
-    LocalizedMessage { locale: String, message: String }
+    LocalizedMessage { locale: String, message: String },
+    /// Describes how long the client should wait before retrying the request.
+    RetryInfo { retry_delay: Duration },
+    /// Debug information for server-side diagnostics, not meant for clients.
+    DebugInfo { stack_entries: Vec<String>, detail: String },
+    /// Describes the cause of the quota or rate-limit being exceeded.
+    QuotaFailure { violations: Vec<QuotaViolation> },
+    /// Describes what preconditions have failed for the request to succeed.
+    PreconditionFailure { violations: Vec<PreconditionViolation> },
+    /// Describes the resource that is being accessed.
+    ResourceInfo { resource_type: String, resource_name: String, owner: String, description: String },
+    /// Contains metadata about the request that clients can attach when filing bug reports.
+    RequestInfo { request_id: String, serving_data: String },
+    /// Links to documentation or other information a client can use to resolve the error.
+    Help { links: Vec<HelpLink> },
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -76,6 +165,10 @@ pub struct ApiError {
     pub message: String,
     /// the field allows messages with additional error information to be included in the error response
     pub details: Vec<ErrorDetails>,
+    /// the original error this `ApiError` was converted from, if any, kept only so
+    /// `std::error::Error::source` can still reach it; never serialized onto the wire.
+    #[serde(skip)]
+    cause: Option<Box<dyn std::error::Error + Send + Sync>>,
 }
 
 impl ApiError {
@@ -84,6 +177,7 @@ impl ApiError {
             code,
             message: message.into(),
             details: Vec::new(),
+            cause: None,
         }
     }
 
@@ -92,7 +186,123 @@ impl ApiError {
             code,
             message: message.into(),
             details,
+            cause: None,
+        }
+    }
+
+    /// Builds an `ApiError` from a wrapped library error (e.g. `sqlx::Error`, `reqwest::Error`),
+    /// walking its `source()` chain into a `DebugInfo` detail so the cause isn't lost for
+    /// logging, while keeping a handle to `err` so `ApiError::source()` still reaches it.
+    pub fn from_source<S: Into<String>>(code: ErrorCode, message: S, err: impl std::error::Error + Send + Sync + 'static) -> Self {
+        let mut stack_entries = Vec::new();
+        let mut cause: Option<&dyn std::error::Error> = Some(&err);
+        while let Some(error) = cause {
+            stack_entries.push(error.to_string());
+            cause = error.source();
+        }
+        let detail = stack_entries.last().cloned().unwrap_or_default();
+
+        Self {
+            code,
+            message: message.into(),
+            details: vec![ErrorDetails::DebugInfo { stack_entries, detail }],
+            cause: Some(Box::new(err)),
+        }
+    }
+
+    /// Attaches an additional detail to the error, returning `self` for fluent chaining.
+    pub fn add_detail(mut self, detail: ErrorDetails) -> Self {
+        self.details.push(detail);
+        self
+    }
+
+    /// Attaches a `RetryInfo` detail telling the client to wait `seconds` before retrying,
+    /// overriding the code's default (see `ApiError::retry_after`) with an app-specific delay.
+    pub fn with_retry_after(self, seconds: i64) -> Self {
+        self.add_detail(ErrorDetails::RetryInfo { retry_delay: Duration::from_secs(seconds) })
+    }
+
+    /// Returns the retry delay in delta-seconds: an explicit `RetryInfo` detail if the caller
+    /// attached one via `with_retry_after`, otherwise the code's own default for `Unavailable`/
+    /// `TooManyRequests` (so `into_response` emits a `Retry-After` header for those codes with
+    /// no extra plumbing at the call site), otherwise `None`.
+    pub fn retry_after(&self) -> Option<i64> {
+        self.details
+            .iter()
+            .find_map(|detail| match detail {
+                ErrorDetails::RetryInfo { retry_delay } => Some(retry_delay.seconds),
+                _ => None,
+            })
+            .or_else(|| self.code.default_retry_after_secs())
+    }
+
+    /// Attaches a `RequestInfo` detail carrying `request_id`, so a sanitized `Internal`/
+    /// `Unknown` response still gives the client something to correlate with server logs (see
+    /// `ApiError::sanitized`). Call this from request-scoped middleware that already generates
+    /// or propagates a request id, before returning the error, e.g.:
+    ///
+    /// ```ignore
+    /// async fn handler(Extension(request_id): Extension<RequestId>) -> Result<Json<Body>, ApiError> {
+    ///     do_thing().await.map_err(|err| {
+    ///         ApiError::from_source(ErrorCode::Internal, "do_thing failed", err)
+    ///             .with_request_id(request_id.to_string())
+    ///     })
+    /// }
+    /// ```
+    pub fn with_request_id<S: Into<String>>(self, request_id: S) -> Self {
+        self.add_detail(ErrorDetails::RequestInfo { request_id: request_id.into(), serving_data: String::new() })
+    }
+
+    /// Returns the full, unsanitized detail of the error (code, developer-facing message and
+    /// all details) for server-side tracing. Use this for logging instead of the serialized
+    /// response body, which is sanitized for `Internal`/`Unknown` codes.
+    pub fn log_repr(&self) -> String {
+        self.to_string()
+    }
+
+    /// Generic message substituted for `Internal`/`Unknown` errors on client-facing transports.
+    /// Attach a `RequestInfo` via `ApiError::with_request_id` before the error is sanitized (see
+    /// `ApiError::sanitized`) if the client should get something to correlate with server logs;
+    /// this message alone carries none.
+    const SANITIZED_MESSAGE: &'static str = "An internal error occurred. Please contact support if the problem persists.";
+
+    /// Whether `detail` is safe to hand to a client, i.e. not `DebugInfo`/`ErrorInfo`, both of
+    /// which may carry stack traces or other implementation detail meant only for `Internal`/
+    /// `Unknown` diagnostics.
+    fn is_client_safe_detail(detail: &ErrorDetails) -> bool {
+        !matches!(detail, ErrorDetails::DebugInfo { .. } | ErrorDetails::ErrorInfo { .. })
+    }
+
+    /// Returns a client-safe copy of the error. For `Internal`/`Unknown` codes, the
+    /// developer-facing `message` and any `DebugInfo`/`ErrorInfo` detail are replaced with a
+    /// generic message, since they may leak stack traces or other implementation detail; any
+    /// other detail — notably a `RequestInfo` attached via `ApiError::with_request_id` — is
+    /// kept, so the client still has something to correlate with server logs. Other codes are
+    /// returned unchanged.
+    fn sanitized(self) -> Self {
+        if !matches!(self.code, ErrorCode::Internal | ErrorCode::Unknown) {
+            return self;
+        }
+
+        let details = self.details.into_iter().filter(Self::is_client_safe_detail).collect();
+
+        Self {
+            code: self.code,
+            message: Self::SANITIZED_MESSAGE.to_string(),
+            details,
+            cause: self.cause,
+        }
+    }
+
+    /// Borrowing equivalent of [`Self::sanitized`], for transports (e.g. gRPC trailers) that
+    /// render the error from a `&self` rather than consuming it.
+    fn client_safe_message_and_details(&self) -> (&str, Vec<&ErrorDetails>) {
+        if !matches!(self.code, ErrorCode::Internal | ErrorCode::Unknown) {
+            return (&self.message, self.details.iter().collect());
         }
+
+        let details = self.details.iter().filter(|detail| Self::is_client_safe_detail(detail)).collect();
+        (Self::SANITIZED_MESSAGE, details)
     }
 }
 
@@ -102,12 +312,78 @@ impl std::fmt::Display for ApiError {
     }
 }
 
-impl std::error::Error for ApiError {}
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause.as_deref().map(|err| err as &(dyn std::error::Error + 'static))
+    }
+}
 
 impl axum::response::IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         let status = self.code.get_http_code();
+        let retry_after = self.retry_after();
+
+        let mut response = (status, Json(self.sanitized())).into_response();
+
+        if let Some(seconds) = retry_after {
+            if let Ok(value) = http::HeaderValue::from_str(&seconds.max(0).to_string()) {
+                response.headers_mut().insert(http::header::RETRY_AFTER, value);
+            }
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debug_info() -> ErrorDetails {
+        ErrorDetails::DebugInfo { stack_entries: vec!["boom".to_string()], detail: "boom".to_string() }
+    }
+
+    fn error_info() -> ErrorDetails {
+        ErrorDetails::ErrorInfo { reason: "BOOM".to_string(), metadata: std::collections::HashMap::new() }
+    }
+
+    #[test]
+    fn sanitized_strips_message_and_debug_details_for_internal_errors() {
+        let err = ApiError::with_details(ErrorCode::Internal, "db exploded", vec![debug_info(), error_info()])
+            .with_request_id("req-1");
+
+        let sanitized = err.sanitized();
+
+        assert_eq!(sanitized.message, ApiError::SANITIZED_MESSAGE);
+        assert_eq!(sanitized.details.len(), 1);
+        assert!(matches!(&sanitized.details[0], ErrorDetails::RequestInfo { request_id, .. } if request_id == "req-1"));
+    }
+
+    #[test]
+    fn sanitized_leaves_non_internal_errors_untouched() {
+        let err = ApiError::with_details(ErrorCode::NotFound, "user 1 not found", vec![error_info()]);
+
+        let sanitized = err.sanitized();
+
+        assert_eq!(sanitized.message, "user 1 not found");
+        assert_eq!(sanitized.details.len(), 1);
+    }
+
+    #[test]
+    fn client_safe_message_and_details_matches_sanitized_for_internal_errors() {
+        let err = ApiError::with_details(ErrorCode::Unknown, "weird", vec![debug_info()]);
+
+        let (message, details) = err.client_safe_message_and_details();
+
+        assert_eq!(message, ApiError::SANITIZED_MESSAGE);
+        assert!(details.is_empty());
+    }
 
-        (status, Json(self)).into_response()
+    #[test]
+    fn retry_after_defaults_for_unavailable_and_too_many_requests() {
+        assert_eq!(ApiError::new(ErrorCode::Unavailable, "x").retry_after(), Some(1));
+        assert_eq!(ApiError::new(ErrorCode::TooManyRequests, "x").retry_after(), Some(30));
+        assert_eq!(ApiError::new(ErrorCode::NotFound, "x").retry_after(), None);
+        assert_eq!(ApiError::new(ErrorCode::Unavailable, "x").with_retry_after(5).retry_after(), Some(5));
     }
 }
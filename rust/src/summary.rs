@@ -0,0 +1,40 @@
+//! Compile-time registry of every error declared through `#[derive(IntoApiError)]`.
+//!
+//! Each annotated variant registers an [`ErrorDescriptor`] into a global `inventory`
+//! collection at link time, so a service can call [`summary`] to dump a complete
+//! `error-codes.json` catalog for documentation and client SDK generation.
+//!
+//! # Feature wiring
+//!
+//! This module and the `inventory::submit!` call emitted by `#[derive(IntoApiError)]` are each
+//! gated behind their own crate's `summary` feature, so enabling it on `errors_convention` must
+//! forward to `errors_convention_derive`'s `summary` feature too, e.g.:
+//!
+//! ```toml
+//! # errors_convention/Cargo.toml
+//! [features]
+//! summary = ["dep:inventory", "errors_convention_derive/summary"]
+//! ```
+//!
+//! Forgetting the `errors_convention_derive/summary` forward doesn't fail the build — the derive
+//! macro just never emits the registration, so [`summary`] compiles, runs, and silently returns
+//! an empty `Vec`, with nothing pointing back at the missing feature forward.
+
+use crate::ErrorCode;
+
+/// One entry in the error catalog: everything needed to document a single declared error.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ErrorDescriptor {
+    pub http_status: u16,
+    pub grpc_code: i32,
+    pub code: ErrorCode,
+    pub reason: &'static str,
+    pub raw_message: &'static str,
+}
+
+inventory::collect!(ErrorDescriptor);
+
+/// Returns every error declared through `#[derive(IntoApiError)]` across the linked binary.
+pub fn summary() -> Vec<ErrorDescriptor> {
+    inventory::iter::<ErrorDescriptor>().cloned().collect()
+}